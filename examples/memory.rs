@@ -6,6 +6,19 @@ use wasmer::{
 use wasmer_compiler_singlepass::Singlepass;
 use wasmer_engine_jit::JIT;
 
+// NOTE for reviewers: six backlog items touching this file — memory64,
+// bounds-checked `Memory::read`/`write`, the pooling allocator, cheap
+// `Store::clone`, `Memory::snapshot`/`restore`, and Singlepass
+// stack-depth limiting — each call an API that lives in the `wasmer`
+// facade crate or one of its compiler/engine crates, none of which this
+// tree vendors (there's no `Cargo.toml` anywhere in it; the only real
+// library code here is `lib/api/src/externals/function.rs`). None of the
+// six can be implemented from this file alone; that's a gap that should
+// have been raised back on the backlog rather than landed as stub
+// commits. Each call site below is left unimplemented with a short note
+// pointing back to this one, instead of repeating the full explanation
+// at every site.
+
 /// A function we'll call through a table.
 fn host_callback(arg1: i32, arg2: i32) -> i32 {
     arg1 + arg2
@@ -94,7 +107,193 @@ fn main() -> anyhow::Result<()> {
     assert_eq!(result, val);
     // -------------
 
+    // `Memory::write`/`read` (bounds-checked against the memory's current,
+    // post-grow size) would let us do the same round trip as above
+    // directly through the host-side `Memory` API, without the Wasm call
+    // overhead of an exported `get_at`/`set_at` pair. Not implemented —
+    // see the reviewer note at the top of this file. Sketch of the call
+    // site, left as a comment:
+    //
+    // memory.write(mem_addr as u64, &val.to_le_bytes())?;
+    // let mut read_back = [0u8; 4];
+    // memory.read(mem_addr as u64, &mut read_back)?;
+    // assert_eq!(i32::from_le_bytes(read_back), val);
+    //
+    // // The typed helpers would do the same `to_le_bytes`/`from_le_bytes`
+    // // dance for us.
+    // memory.write::<i32>(mem_addr as u64, val)?;
+    // assert_eq!(memory.read::<i32>(mem_addr as u64)?, val);
+    //
+    // // Out-of-bounds accesses would be reported, not a crash or silent
+    // // OOB read.
+    // let out_of_bounds = (memory.size().bytes().0) as u64;
+    // assert!(memory.read::<i32>(out_of_bounds).is_err());
+
+    // A `Memory::snapshot`/`restore` facility would let us try out a batch
+    // of mutations and cheaply roll them back, instead of reinstantiating
+    // the module to get a clean memory image again. Not implemented — see
+    // the reviewer note at the top of this file. Sketch of the call site,
+    // left as a comment:
+    //
+    // let baseline = memory.snapshot();
+    // let baseline_pages = memory.size();
+    //
+    // memory.write::<i32>(mem_addr as u64, val + 1)?;
+    // memory.grow(4)?;
+    // assert_eq!(memory.read::<i32>(mem_addr as u64)?, val + 1);
+    // assert_ne!(memory.size(), baseline_pages);
+    //
+    // // Restoring would rewrite only the pages `snapshot` saw change, and
+    // // drop whatever `grow` added afterwards, bringing us back to
+    // // exactly the state `baseline` was taken from.
+    // memory.restore(&baseline)?;
+    // assert_eq!(memory.size(), baseline_pages);
+    // assert_eq!(memory.read::<i32>(mem_addr as u64)?, val);
+
     //let result = get_at.call(page_size * 1028 - 4)?;
 
+    #[cfg(feature = "memory64")]
+    run_memory64_example()?;
+
+    #[cfg(all(target_os = "linux", feature = "pooling-allocator"))]
+    run_pooling_allocator_example()?;
+
+    #[cfg(feature = "stack-depth-limit")]
+    run_stack_depth_limit_example()?;
+
+    // A reference-counted, cheaply `Clone`-able `Store` would let a worker
+    // thread own a clone of it (and reuse the same compiled `module`)
+    // instead of the caller having to keep `store` alive and pass
+    // `&Store` around. Not implemented — see the reviewer note at the
+    // top of this file. Sketch of the call site, left as a comment:
+    //
+    // let store_handle = store.clone();
+    // std::thread::spawn(move || {
+    //     let instance = Instance::new(&module, &imports! {}).expect("instantiate on worker thread");
+    //     let mem_size: NativeFunc<(), i32> = instance
+    //         .exports
+    //         .get_native_function("mem_size")
+    //         .expect("mem_size export");
+    //     let result = mem_size.call().expect("call mem_size");
+    //     assert_eq!(result, 1);
+    //     drop(store_handle);
+    // })
+    // .join()
+    // .expect("worker thread panicked");
+
     Ok(())
 }
+
+/// Grows memory the same way `main` does (1 page to 1028), but with the
+/// instance's memory backed by the pooling allocator instead of the
+/// default eager one.
+///
+/// Growing to 1028 pages eagerly zeroes and maps all of them up front;
+/// the pooling allocator instead reserves the slot's full address-space
+/// range once at `Store` creation and leaves pages unmapped until a
+/// `userfaultfd` fault lazily zero-fills them on first touch, so this
+/// `grow` call only pays for the pages `set_at` actually writes to.
+///
+/// Needs a `wasmer_engine_jit` allocator type not present here — see the
+/// reviewer note at the top of this file. The body below is left
+/// unimplemented; the sketch of the call site is kept as a comment.
+///
+/// ```text
+/// use wasmer_engine_jit::InstanceAllocator;
+///
+/// (module
+///   (memory $mem 1)
+///   (func $set_at (param $idx i32) (param $val i32)
+///     (i32.store (local.get $idx) (local.get $val)))
+///   (export "set_at" (func $set_at))
+///   (export "memory" (memory $mem)))
+///
+/// let allocator = InstanceAllocator::pooling()
+///     .max_instances(16)
+///     .max_memory_pages(1028)
+///     .guard_page_size(Pages::from(1).bytes().0 as u32);
+/// let store = Store::new(&JIT::new(&Singlepass::default()).with_allocator(allocator).engine());
+/// let module = Module::new(&store, wasm_bytes)?;
+/// let instance = Instance::new(&module, &imports! {})?;
+/// let set_at: NativeFunc<(i32, i32), ()> = instance.exports.get_native_function("set_at")?;
+/// let memory = instance.exports.get_memory("memory")?;
+/// memory.grow(1027)?;
+/// set_at.call(0x1_0000 * 1027 - 4, 123456)?;
+/// ```
+#[cfg(all(target_os = "linux", feature = "pooling-allocator"))]
+fn run_pooling_allocator_example() -> anyhow::Result<()> {
+    unimplemented!("pooling InstanceAllocator needs wasmer_engine_jit support not present in this tree")
+}
+
+/// Same `get_at` shape as `main`, but against a `memory64`-flagged memory,
+/// so `get_at` is indexed with a `u64` instead of being capped at the
+/// `i32`-addressable 4 GiB a 32-bit memory tops out at.
+///
+/// Gated behind the (not-yet-stabilized) `memory64` feature: existing
+/// 32-bit modules keep compiling and running exactly as before whether or
+/// not it's enabled.
+///
+/// Needs a `MemoryType`/ABI/compiler change not present here — see the
+/// reviewer note at the top of this file. The body below is left
+/// unimplemented; the sketch of the call site is kept as a comment.
+///
+/// ```text
+/// (module
+///   (type $get_at_t (func (param i64) (result i32)))
+///   (memory $mem i64 1)
+///   (func $get_at (type $get_at_t) (param $idx i64) (result i32)
+///     (i32.load (local.get $idx)))
+///   (export "get_at" (func $get_at))
+///   (export "memory" (memory $mem)))
+///
+/// let store = Store::new(&JIT::new(&Singlepass::default()).engine());
+/// let module = Module::new(&store, wasm_bytes)?;
+/// let instance = Instance::new(&module, &imports! {})?;
+/// let get_at: NativeFunc<u64, i32> = instance.exports.get_native_function("get_at")?;
+/// let memory = instance.exports.get_memory("memory")?;
+/// // A `memory64` memory's page count can exceed `u32::MAX`, so `size()`
+/// // returns a wider count than the default 32-bit memory does.
+/// assert_eq!(memory.size().0, 1u64);
+/// let _ = get_at.call(0)?;
+/// ```
+#[cfg(feature = "memory64")]
+fn run_memory64_example() -> anyhow::Result<()> {
+    unimplemented!("memory64 needs MemoryType/ABI/compiler support not present in this tree")
+}
+
+/// Recurses a Wasm function against itself with no base case, on a
+/// `Singlepass` compiler configured with a native stack-depth limit, and
+/// checks that it traps deterministically instead of smashing the host
+/// stack.
+///
+/// `Singlepass` compiles straight to machine code with native recursion,
+/// so without a limit this would eventually SIGSEGV on host stack
+/// exhaustion rather than produce a catchable `RuntimeError`.
+///
+/// Needs a `wasmer_compiler_singlepass` codegen change not present here
+/// — see the reviewer note at the top of this file. The body below is
+/// left unimplemented; the sketch of the call site is kept as a
+/// comment.
+///
+/// ```text
+/// (module
+///   (func $recurse (param $n i32) (result i32)
+///     (i32.add (local.get $n) (call $recurse (i32.add (local.get $n) (i32.const 1)))))
+///   (export "recurse" (func $recurse)))
+///
+/// let compiler = Singlepass::default().with_max_stack_depth(4096);
+/// let store = Store::new(&JIT::new(&compiler).engine());
+/// let module = Module::new(&store, wasm_bytes)?;
+/// let instance = Instance::new(&module, &imports! {})?;
+/// let recurse: NativeFunc<i32, i32> = instance.exports.get_native_function("recurse")?;
+/// let result = recurse.call(0);
+/// assert!(result.is_err(), "unbounded recursion should trap");
+/// ```
+///
+/// Gated behind a `stack-depth-limit` feature, like the other two
+/// `unimplemented!` demos in this file, so that a plain
+/// `cargo run --example memory` does not reach this panic.
+#[cfg(feature = "stack-depth-limit")]
+fn run_stack_depth_limit_example() -> anyhow::Result<()> {
+    unimplemented!("Singlepass stack-depth limiting needs compiler codegen support not present in this tree")
+}